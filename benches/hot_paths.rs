@@ -0,0 +1,102 @@
+use criterion::{criterion_group, BatchSize, Criterion};
+use simple_blockchain::core::{testutil::ChainBuilder, Block};
+
+// Low enough that a mining iteration takes milliseconds rather than seconds.
+const MINING_DIFFICULTY: u8 = 10;
+
+const FULL_BLOCK_TRANSACTIONS: usize = 1999;
+
+const LONG_CHAIN_BLOCKS: usize = 10_000;
+
+const PENDING_TRANSFERS: usize = 10;
+
+fn mining(c: &mut Criterion) {
+    let mut cb = ChainBuilder::new(2, 0);
+    let unsolved = bincode::serialize(&cb.storage().prepare_mineable_block(None, None).unwrap()).unwrap();
+    // Every iteration starts from the same nonce, so the work is identical.
+    c.bench_function("mine block", |b| {
+        b.iter_batched(
+            || bincode::deserialize::<Block>(&unsolved).unwrap(),
+            |mut block| assert!(block.solve_hash_challenge(MINING_DIFFICULTY, None)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+// NOTE that receiving a full block currently takes around a minute, so this
+// group alone runs for several minutes even at the minimum sample size.
+fn full_block(c: &mut Criterion) {
+    let mut cb = ChainBuilder::new(50, 1);
+    cb.build(40, 0);
+    // The node receiving the block has not seen any of its transactions.
+    let node = cb.storage().fork_in_memory().unwrap();
+    let mut transfers = Vec::new();
+    while transfers.len() < FULL_BLOCK_TRANSACTIONS {
+        transfers.extend(cb.transfer());
+    }
+    let block = cb.block_with(transfers);
+    let serialized = bincode::serialize(&block).unwrap();
+    let mut spoiled = bincode::deserialize::<Block>(&serialized).unwrap();
+    cb.spoil_last_signature(&mut spoiled);
+
+    c.bench_function("receive full block", |b| {
+        b.iter_batched(
+            || node.fork_in_memory().unwrap(),
+            |mut node| node.receive_block(&block).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+    // The spoiled block is rejected before it touches the database, so this is
+    // the cost of verifying its signatures in parallel. Run with
+    // RAYON_NUM_THREADS=1 for the cost of verifying them serially.
+    c.bench_function("verify full block signatures", |b| b.iter(|| assert!(node.validate_block(&spoiled).is_err())));
+    c.bench_function("serialize full block", |b| b.iter(|| bincode::serialize(&block).unwrap()));
+    c.bench_function("deserialize full block", |b| b.iter(|| bincode::deserialize::<Block>(&serialized).unwrap()));
+}
+
+fn wallet_balance(c: &mut Criterion) {
+    let mut cb = ChainBuilder::new(4, 2);
+    cb.build(200, 10);
+    let wallet = cb.wallets()[0].public_key_hash().clone();
+    c.bench_function("wallet balance", |b| b.iter(|| cb.storage().find_wallet_balance(&wallet, 1).unwrap()));
+}
+
+// NOTE that building the chain takes around fifteen minutes, since every
+// received block is still checked against the transactions of its ancestors,
+// so this group only runs when LONG_CHAIN_BENCHES is set.
+fn long_chain(c: &mut Criterion) {
+    let mut cb = ChainBuilder::new(4, 3);
+    cb.build(LONG_CHAIN_BLOCKS, 0);
+    let wallet = cb.wallets()[0].public_key_hash().clone();
+    c.bench_function("wallet balance on a long chain", |b| {
+        b.iter(|| cb.storage().find_wallet_balance(&wallet, 1).unwrap())
+    });
+    // Pending transactions make preparation check each of them for conflicts.
+    for _ in 0..PENDING_TRANSFERS {
+        cb.transfer();
+    }
+    c.bench_function("prepare block on a long chain", |b| {
+        b.iter(|| cb.storage().prepare_mineable_block(None, None).unwrap())
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = mining, full_block, wallet_balance
+}
+
+criterion_group! {
+    name = long_benches;
+    config = Criterion::default().sample_size(10);
+    targets = long_chain
+}
+
+// Like criterion_main!, except for the opt-in group.
+fn main() {
+    benches();
+    if std::env::var_os("LONG_CHAIN_BENCHES").is_some() {
+        long_benches();
+    }
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,67 @@
+use super::*;
+
+// The same limits as BIP 37, so that a filter supplied by a client cannot
+// make matching arbitrarily expensive.
+pub const MAX_BLOOM_FILTER_BYTES: usize = 36000;
+pub const MAX_BLOOM_HASH_FUNCS: u32 = 50;
+
+// A bloom filter of wallet hashes. Light clients send one to learn about the
+// transactions that concern them without revealing exactly which addresses
+// are theirs; the false positive rate controls how much they reveal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    hash_funcs: u32,
+    tweak: u32,
+}
+
+impl BloomFilter {
+    // The tweak should be chosen randomly by the client, so that filters of
+    // different clients set different bits for the same wallet.
+    pub fn new(expected_items: usize, false_positive_rate: f64, tweak: u32) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 1.0);
+        let ln2 = std::f64::consts::LN_2;
+        let bytes = ((-n * p.ln() / (ln2 * ln2)) / 8.0).ceil() as usize;
+        let bytes = bytes.clamp(1, MAX_BLOOM_FILTER_BYTES);
+        let hash_funcs = ((bytes * 8) as f64 / n * ln2).round() as u32;
+        BloomFilter { bits: vec![0; bytes], hash_funcs: hash_funcs.clamp(1, MAX_BLOOM_HASH_FUNCS), tweak }
+    }
+
+    // NOTE that wallet hashes are already uniformly distributed, so the
+    // tweaked digest only serves to decorrelate filters with different tweaks.
+    // The bit indices are derived from it by double hashing.
+    fn bit_indices<'a>(self: &'a Self, h: &Hash) -> impl Iterator<Item = usize> + 'a {
+        let mut b = self.tweak.to_le_bytes().to_vec();
+        b.extend_from_slice(&h.0);
+        let d = Hash::sha256(&b).0;
+        let h1 = u64::from_le_bytes([d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]]);
+        let h2 = u64::from_le_bytes([d[8], d[9], d[10], d[11], d[12], d[13], d[14], d[15]]);
+        let bit_count = self.bits.len() as u64 * 8;
+        (0..self.hash_funcs.min(MAX_BLOOM_HASH_FUNCS) as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+    }
+
+    // An empty or oversized filter, which only a misbehaving client would
+    // send, has no bits to index; it ignores insertions and matches nothing.
+    fn is_usable(self: &Self) -> bool {
+        !self.bits.is_empty() && self.bits.len() <= MAX_BLOOM_FILTER_BYTES && self.hash_funcs != 0
+    }
+
+    pub fn insert(self: &mut Self, h: &Hash) {
+        if !self.is_usable() {
+            return;
+        }
+        let indices: Vec<usize> = self.bit_indices(h).collect();
+        for i in indices.into_iter() {
+            self.bits[i / 8] |= 1 << (i % 8);
+        }
+    }
+
+    pub fn contains(self: &Self, h: &Hash) -> bool {
+        if !self.is_usable() {
+            return false;
+        }
+        self.bit_indices(h).all(|i| self.bits[i / 8] & (1 << (i % 8)) != 0)
+    }
+}
@@ -0,0 +1,168 @@
+use super::*;
+use serde_json::{json, Value};
+use std::convert::TryFrom;
+
+// A field-named JSON form of blocks and transactions for web frontends and
+// other tooling, unlike the positional bincode form used everywhere else.
+// Hashes, keys and signatures are hex. Amounts and nonces are decimal
+// strings, since they may exceed the integers JavaScript represents exactly.
+
+fn field<'a>(v: &'a Value, key: &'static str) -> Result<&'a Value, BlockchainError> {
+    v.get(key).ok_or(BlockchainError::InvalidJson(key))
+}
+
+fn str_field<'a>(v: &'a Value, key: &'static str) -> Result<&'a str, BlockchainError> {
+    field(v, key)?.as_str().ok_or(BlockchainError::InvalidJson(key))
+}
+
+fn u64_field(v: &Value, key: &'static str) -> Result<u64, BlockchainError> {
+    field(v, key)?.as_u64().ok_or(BlockchainError::InvalidJson(key))
+}
+
+fn decimal_field(v: &Value, key: &'static str) -> Result<u64, BlockchainError> {
+    let s = str_field(v, key)?;
+    // NOTE that parse would also accept a leading plus sign.
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(BlockchainError::InvalidJson(key));
+    }
+    s.parse().map_err(|_| BlockchainError::InvalidJson(key))
+}
+
+fn bytes_field(v: &Value, key: &'static str) -> Result<Vec<u8>, BlockchainError> {
+    hex_decode(str_field(v, key)?).map_err(|_| BlockchainError::InvalidJson(key))
+}
+
+fn hash_field(v: &Value, key: &'static str) -> Result<Hash, BlockchainError> {
+    Hash::from_hex(str_field(v, key)?).map_err(|_| BlockchainError::InvalidJson(key))
+}
+
+fn array_field<'a>(v: &'a Value, key: &'static str) -> Result<&'a Vec<Value>, BlockchainError> {
+    field(v, key)?.as_array().ok_or(BlockchainError::InvalidJson(key))
+}
+
+// A missing field and null both mean None.
+fn optional<'a, T>(
+    v: &'a Value, key: &'static str, parse: impl FnOnce(&'a Value, &'static str) -> Result<T, BlockchainError>,
+) -> Result<Option<T>, BlockchainError> {
+    match v.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => parse(v, key).map(Some),
+    }
+}
+
+fn payer_field(v: &Value, key: &'static str) -> Result<PayerPublicKey, BlockchainError> {
+    Some(PayerPublicKey(bytes_field(v, key)?)).filter(|p| p.check_len()).ok_or(BlockchainError::InvalidJson(key))
+}
+
+// NOTE that a DER-encoded secp256k1 signature takes at most 72 bytes.
+fn signature_field(v: &Value, key: &'static str) -> Result<Signature, BlockchainError> {
+    let b = bytes_field(v, key)?;
+    if b.is_empty() || b.len() > 72 {
+        return Err(BlockchainError::InvalidJson(key));
+    }
+    Ok(Signature(b))
+}
+
+impl Transaction {
+    pub fn to_json(self: &Self) -> Value {
+        json!({
+            "version": self.version,
+            "transaction_hash": self.transaction_hash.display_hex(),
+            "payer": hex_encode(&self.payer.0),
+            "inputs": self.inputs.iter().map(|i| json!({
+                "transaction_hash": i.outpoint.transaction_hash.display_hex(),
+                "index": i.outpoint.index,
+            })).collect::<Vec<_>>(),
+            "outputs": self.outputs.iter().map(|o| json!({
+                "amount": o.amount.0.to_string(),
+                "recipient_hash": o.recipient_hash.display_hex(),
+            })).collect::<Vec<_>>(),
+            "signature": hex_encode(&self.signature.0),
+            "cosigners": self.cosigners.iter().map(|c| json!({
+                "payer": hex_encode(&c.payer.0),
+                "signature": hex_encode(&c.signature.0),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    // NOTE that the hash is recomputed from the contents, like bincode
+    // deserialization does, and must agree with the one given. The signatures
+    // are not verified.
+    pub fn from_json(v: &Value) -> Result<Self, BlockchainError> {
+        let inputs = array_field(v, "inputs")?
+            .iter()
+            .map(|i| {
+                let index = u16::try_from(u64_field(i, "index")?).map_err(|_| BlockchainError::InvalidJson("index"))?;
+                let transaction_hash = hash_field(i, "transaction_hash")?;
+                Ok(TransactionInput { outpoint: OutPoint { transaction_hash, index } })
+            })
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+        let outputs = array_field(v, "outputs")?
+            .iter()
+            .map(|o| {
+                let amount = Amount::try_from(decimal_field(o, "amount")?)?;
+                Ok(TransactionOutput { amount, recipient_hash: hash_field(o, "recipient_hash")? })
+            })
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+        let cosigners = array_field(v, "cosigners")?
+            .iter()
+            .map(|c| Ok(Cosignature { payer: payer_field(c, "payer")?, signature: signature_field(c, "signature")? }))
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+        let version = u8::try_from(u64_field(v, "version")?).map_err(|_| BlockchainError::InvalidJson("version"))?;
+        let mut txn = Transaction {
+            version,
+            payer: payer_field(v, "payer")?,
+            inputs,
+            outputs,
+            signature: signature_field(v, "signature")?,
+            cosigners,
+            transaction_hash: Hash::zeroes(),
+        };
+        txn.recalc_hash();
+        if txn.transaction_hash != hash_field(v, "transaction_hash")? {
+            return Err(BlockchainError::InvalidJson("transaction_hash"));
+        }
+        Ok(txn)
+    }
+}
+
+impl Block {
+    pub fn to_json(self: &Self) -> Value {
+        json!({
+            "version": self.version,
+            "block_hash": self.block_hash.display_hex(),
+            "parent_hash": self.parent_hash.as_ref().map(|h| h.display_hex()),
+            "nonce": self.nonce.to_string(),
+            "timestamp": self.timestamp,
+            "merkle_root": self.merkle_root.display_hex(),
+            "coinbase_message": self.coinbase_message.as_ref().map(|m| hex_encode(m)),
+            "transactions": self.transactions.iter().map(|t| t.to_json()).collect::<Vec<_>>(),
+        })
+    }
+
+    // NOTE that like a block received from a peer, the result is not
+    // validated beyond the lengths of its fields; receive_block does that.
+    pub fn from_json(v: &Value) -> Result<Self, BlockchainError> {
+        let coinbase_message = optional(v, "coinbase_message", bytes_field)?;
+        if coinbase_message.as_ref().map_or(0, |m| m.len()) > MAX_COINBASE_MESSAGE_BYTES {
+            return Err(BlockchainError::InvalidJson("coinbase_message"));
+        }
+        let nonce = decimal_field(v, "nonce")?;
+        if nonce >= 1 << 63 {
+            return Err(BlockchainError::InvalidJson("nonce"));
+        }
+        Ok(Block {
+            version: u8::try_from(u64_field(v, "version")?).map_err(|_| BlockchainError::InvalidJson("version"))?,
+            nonce,
+            timestamp: u64_field(v, "timestamp")?,
+            merkle_root: hash_field(v, "merkle_root")?,
+            transactions: array_field(v, "transactions")?
+                .iter()
+                .map(Transaction::from_json)
+                .collect::<Result<Vec<_>, BlockchainError>>()?,
+            parent_hash: optional(v, "parent_hash", hash_field)?,
+            coinbase_message,
+            block_hash: hash_field(v, "block_hash")?,
+        })
+    }
+}
@@ -0,0 +1,32 @@
+use super::*;
+
+// What a policy may inspect besides the transaction itself. Both are known
+// only once the transaction has passed the consensus checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyContext {
+    pub fee: Amount,
+    // The size of the serialized transaction in bytes.
+    pub size: usize,
+}
+
+// A local rule deciding which valid transactions this node accepts into its
+// pool of pending transactions. Policies are never applied to the
+// transactions of received blocks, which only need to satisfy consensus.
+pub trait TransactionPolicy: std::fmt::Debug + Send {
+    fn check(&self, tx: &Transaction, ctx: &PolicyContext) -> Result<(), &'static str>;
+}
+
+// Rejects transactions paying less than the given fee per serialized byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinFeePolicy {
+    pub min_fee_per_byte: u64,
+}
+
+impl TransactionPolicy for MinFeePolicy {
+    fn check(&self, _tx: &Transaction, ctx: &PolicyContext) -> Result<(), &'static str> {
+        if u128::from(ctx.fee.0) < u128::from(self.min_fee_per_byte) * ctx.size as u128 {
+            return Err("The tentative transaction pays less than the minimum fee rate of this node");
+        }
+        Ok(())
+    }
+}
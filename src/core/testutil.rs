@@ -0,0 +1,203 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    DoubleSpend,
+    BadSignature,
+    OverSpend,
+}
+
+pub const ALL_FAULTS: [Fault; 3] = [Fault::DoubleSpend, Fault::BadSignature, Fault::OverSpend];
+
+// Mainnet parameters, except that rewards can be spent in the next block so
+// that tests need not mine a hundred blocks first.
+pub fn params() -> NetworkParams { NetworkParams { coinbase_maturity: 1, ..NetworkParams::mainnet() } }
+
+// Options for a storage in tests. Its default wallet is generated rather
+// than read from or saved to the user's wallet file.
+pub fn options() -> StorageOptions {
+    StorageOptions { default_wallet: Some(Wallet::new()), params: params(), ..StorageOptions::default() }
+}
+
+// An in-memory storage with the given default wallet.
+pub fn storage(default_wallet: &Wallet) -> BlockchainStorage {
+    BlockchainStorage::new(StorageOptions { default_wallet: Some(default_wallet.clone()), ..options() })
+}
+
+// Mines a block on top of the tip with the default wallet as the miner, and
+// receives it.
+pub fn mine_block(bs: &mut BlockchainStorage) -> Block { mine_block_with(bs, None) }
+
+// Like mine_block, but with the reward going to the given wallet.
+pub fn mine_block_by(bs: &mut BlockchainStorage, miner: &Wallet) -> Block { mine_block_with(bs, Some(miner)) }
+
+fn mine_block_with(bs: &mut BlockchainStorage, miner: Option<&Wallet>) -> Block {
+    let mut block = bs.prepare_mineable_block(miner, None).unwrap();
+    let difficulty = bs.required_difficulty(block.parent_hash.as_ref()).unwrap();
+    assert!(block.solve_hash_challenge(difficulty, None));
+    bs.receive_block(&block).unwrap();
+    block
+}
+
+fn spoil_signature(txn: &mut Transaction) {
+    let last = txn.signature.0.len() - 1;
+    txn.signature.0[last] ^= 1;
+    txn.recalc_hash();
+}
+
+// Builds random but valid chains. The choices made are deterministic for a
+// given seed, although keys and signatures are not.
+pub struct ChainBuilder {
+    storage: BlockchainStorage,
+    wallets: Vec<Wallet>,
+    rng_state: u64,
+}
+
+impl ChainBuilder {
+    pub fn new(wallet_count: usize, seed: u64) -> Self {
+        assert!(wallet_count >= 2);
+        let wallets: Vec<Wallet> = (0..wallet_count).map(|_| Wallet::new()).collect();
+        ChainBuilder { storage: storage(&wallets[0]), wallets, rng_state: seed }
+    }
+
+    pub fn storage(self: &mut Self) -> &mut BlockchainStorage { &mut self.storage }
+
+    pub fn wallets(self: &Self) -> &[Wallet] { &self.wallets }
+
+    // SplitMix64, so that any seed (including zero) works.
+    fn next_random(self: &mut Self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn random_wallet_index(self: &mut Self) -> usize { (self.next_random() % self.wallets.len() as u64) as usize }
+
+    fn solve(self: &Self, block: &mut Block) {
+        let difficulty = self.storage.required_difficulty(block.parent_hash.as_ref()).unwrap();
+        assert!(block.solve_hash_challenge(difficulty, None))
+    }
+
+    pub fn mine_block(self: &mut Self) -> Block {
+        let miner = self.random_wallet_index();
+        let mut block = self.storage.prepare_mineable_block(Some(&self.wallets[miner]), None).unwrap();
+        self.solve(&mut block);
+        self.storage.receive_block(&block).unwrap();
+        block
+    }
+
+    // Sends a random amount between two random wallets, if the payer has any
+    // coins at all.
+    pub fn transfer(self: &mut Self) -> Option<Transaction> {
+        let payer = self.random_wallet_index();
+        let recipient = (payer + 1 + self.next_random() as usize % (self.wallets.len() - 1)) % self.wallets.len();
+        let balance = self.storage.find_wallet_balance(self.wallets[payer].public_key_hash(), 0).unwrap();
+        if balance == 0 {
+            return None;
+        }
+        let amount = Amount(1 + self.next_random() % (balance.min(Amount::MAX_MONEY.0) / 2).max(1));
+        let recipient_hash = self.wallets[recipient].public_key_hash().clone();
+        Some(self.storage.create_simple_transaction(Some(&self.wallets[payer]), amount, &recipient_hash).unwrap())
+    }
+
+    pub fn build(self: &mut Self, block_count: usize, transfers_per_block: usize) -> &mut Self {
+        for _ in 0..block_count {
+            for _ in 0..transfers_per_block {
+                self.transfer();
+            }
+            self.mine_block();
+        }
+        self
+    }
+
+    // Produces a solved block on top of the tip containing the given
+    // transactions, in addition to the reward. The block is not received.
+    // This is much faster than prepare_mineable_block for large blocks, but
+    // the transactions must be valid in the order given.
+    pub fn block_with(self: &mut Self, transactions: Vec<Transaction>) -> Block {
+        let miner = self.random_wallet_index();
+        let parent_hash = self.storage.get_longest_chain().unwrap().next().map(|(h, _)| h);
+        let height = BlockchainStorage::next_block_height_raw(&self.storage.conn, parent_hash.as_ref()).unwrap();
+        let reward = self.storage.params().block_reward_at_height(height);
+        let mut block = Block::new_mine_block(&self.wallets[miner], reward, None);
+        block.timestamp = block.timestamp.max(self.storage.min_block_timestamp(parent_hash.as_ref()).unwrap());
+        block.transactions.extend(transactions);
+        block.parent_hash = parent_hash;
+        self.solve(&mut block);
+        block
+    }
+
+    // Produces a solved block on top of the given one. It is credited with as
+    // much work as any block at its height, so it does not take over a chain
+    // of the same length received before it. The block is not received.
+    pub fn fork_block(self: &mut Self, parent_hash: &Hash) -> Block {
+        let miner = self.random_wallet_index();
+        let height = BlockchainStorage::next_block_height_raw(&self.storage.conn, Some(parent_hash)).unwrap();
+        let reward = self.storage.params().block_reward_at_height(height);
+        let mut block = Block::new_mine_block(&self.wallets[miner], reward, None);
+        block.parent_hash = Some(parent_hash.clone());
+        block.set_timestamp(block.timestamp.max(self.storage.min_block_timestamp(Some(parent_hash)).unwrap()));
+        self.solve(&mut block);
+        block
+    }
+
+    // Finds a confirmed output to spend, together with its owner.
+    fn confirmed_output(self: &mut Self) -> (usize, TransactionInput, Amount) {
+        let start = self.random_wallet_index();
+        let n = self.wallets.len();
+        for i in (0..n).map(|i| (start + i) % n) {
+            let t = self.storage.conn.transaction().unwrap();
+            let maturity = self.storage.params.coinbase_maturity;
+            let found = BlockchainStorage::find_available_spend(&t, self.wallets[i].public_key_hash(), maturity)
+                .unwrap()
+                .find(|&(_, _, conf)| conf > 0);
+            if let Some((input, amount, _)) = found {
+                return (i, input, amount);
+            }
+        }
+        panic!("no confirmed outputs; mine a block first")
+    }
+
+    // Spoils the signature of the last transaction of a block and solves it
+    // again, so that it is rejected only once every signature is verified.
+    pub fn spoil_last_signature(self: &Self, block: &mut Block) {
+        spoil_signature(block.transactions.last_mut().unwrap());
+        self.solve(block);
+    }
+
+    // Produces a block with a solved hash challenge that contains the given
+    // fault. The block is not received.
+    pub fn faulty_block(self: &mut Self, fault: Fault) -> Block {
+        let (owner, input, amount) = self.confirmed_output();
+        let recipient = (owner + 1) % self.wallets.len();
+        let spend = |to: usize, amount: Amount| {
+            TransactionOutput { amount, recipient_hash: self.wallets[to].public_key_hash().clone() }
+        };
+        let faulty_txns = match fault {
+            Fault::DoubleSpend => {
+                let same_input = TransactionInput { outpoint: input.outpoint.clone() };
+                vec![
+                    self.wallets[owner].create_raw_transaction(vec![input], vec![spend(recipient, amount)]),
+                    self.wallets[owner].create_raw_transaction(vec![same_input], vec![spend(owner, amount)]),
+                ]
+            }
+            Fault::BadSignature => {
+                let mut txn = self.wallets[owner].create_raw_transaction(vec![input], vec![spend(recipient, amount)]);
+                spoil_signature(&mut txn);
+                vec![txn]
+            }
+            Fault::OverSpend => {
+                let overspend = spend(recipient, Amount(amount.0 + 1));
+                vec![self.wallets[owner].create_raw_transaction(vec![input], vec![overspend])]
+            }
+        };
+        let miner = self.random_wallet_index();
+        let mut block = self.storage.prepare_mineable_block(Some(&self.wallets[miner]), None).unwrap();
+        block.transactions.truncate(1);
+        block.transactions.extend(faulty_txns);
+        self.solve(&mut block);
+        block
+    }
+}
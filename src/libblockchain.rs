@@ -1,2 +1,17 @@
 pub mod core;
 pub mod net;
+
+// The types that make up the supported API. Everything else in core is an
+// implementation detail, even where it happens to be public.
+pub mod prelude {
+    pub use crate::core::{
+        Amount, Block, BlockchainError, BlockchainStats, BlockchainStorage, CompactBlock, Cosignature, Hash,
+        MempoolStatus, MineOrdering, NetworkParams, OutPoint, PayerPublicKey, ReadOnlyStorage, Signature, SignedAmount,
+        StorageOptions, TentativeStatus, Transaction, TransactionInput, TransactionOutput, VerifyStage, Wallet,
+        WalletBalance,
+    };
+    pub use crate::core::{
+        filter::BloomFilter,
+        policy::{MinFeePolicy, PolicyContext, TransactionPolicy},
+    };
+}